@@ -8,7 +8,7 @@
 //! #[tokio::main]
 //! async fn main() {
 //!     let is_dev = std::env::var("ENV").unwrap_or_default() != "production";
-//!     slogx::init(is_dev, 8080, "my-service").await;
+//!     slogx::init(is_dev, 8080, "my-service", 100, None, slogx::StacktracePolicy::ErrorsOnly).await;
 //!
 //!     slogx::info!("Server started", {"port": 8080});
 //! }
@@ -16,21 +16,23 @@
 
 use backtrace::Backtrace;
 use chrono::Utc;
+use flate2::{write::GzEncoder, Compression};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::RwLock;
-use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, RwLock};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
 use uuid::Uuid;
 
 /// Global singleton instance.
 static INSTANCE: OnceLock<SlogX> = OnceLock::new();
 
 /// Log levels matching the frontend.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum LogLevel {
     Debug,
@@ -70,9 +72,47 @@ pub struct LogEntry {
     pub args: Vec<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stacktrace: Option<String>,
+    /// Causal chain from `std::error::Error::source()`, immediate cause first. Does
+    /// not include the top-level error itself, and the top-level error's message is
+    /// not added to `args` either: it appears only if the caller passed it there.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub error_chain: Vec<String>,
     pub metadata: LogMetadata,
 }
 
+/// Controls when `LogEntry::new` pays the cost of capturing a stacktrace.
+///
+/// Walking and formatting a backtrace on every call is expensive, so services with
+/// high Debug/Info volume should restrict it to the levels that actually need it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StacktracePolicy {
+    /// Never capture a stacktrace.
+    Never,
+    /// Only capture a stacktrace for `LogLevel::Error`.
+    ErrorsOnly,
+    /// Capture a stacktrace at or above the given level.
+    AtLevel(LogLevel),
+    /// Always capture a stacktrace, regardless of level.
+    Always,
+}
+
+impl Default for StacktracePolicy {
+    fn default() -> Self {
+        StacktracePolicy::Always
+    }
+}
+
+impl StacktracePolicy {
+    fn should_capture(&self, level: LogLevel) -> bool {
+        match self {
+            StacktracePolicy::Never => false,
+            StacktracePolicy::ErrorsOnly => level == LogLevel::Error,
+            StacktracePolicy::AtLevel(min) => level >= *min,
+            StacktracePolicy::Always => true,
+        }
+    }
+}
+
 /// Build a clean stacktrace, filtering out only slogx internal frames.
 fn build_clean_stacktrace() -> String {
     let bt = Backtrace::new();
@@ -118,7 +158,8 @@ fn build_clean_stacktrace() -> String {
 }
 
 impl LogEntry {
-    /// Create a new log entry.
+    /// Create a new log entry, capturing a stacktrace only if `stacktrace_policy`
+    /// requires one at `level`.
     pub fn new(
         level: LogLevel,
         args: Vec<Value>,
@@ -126,15 +167,19 @@ impl LogEntry {
         file: Option<&str>,
         line: Option<u32>,
         function: Option<&str>,
+        stacktrace_policy: StacktracePolicy,
     ) -> Self {
-        let stacktrace = build_clean_stacktrace();
+        let stacktrace = stacktrace_policy
+            .should_capture(level)
+            .then(build_clean_stacktrace);
 
         Self {
             id: Uuid::new_v4().to_string(),
             timestamp: Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
             level,
             args,
-            stacktrace: Some(stacktrace),
+            stacktrace,
+            error_chain: Vec::new(),
             metadata: LogMetadata {
                 file: file.map(|s| s.to_string()),
                 line,
@@ -144,17 +189,156 @@ impl LogEntry {
             },
         }
     }
+
+    /// Attach the causal chain from `err.source()`, immediate cause first. `err`'s own
+    /// message is not included, here or in `args`.
+    fn with_error_chain(mut self, err: &dyn std::error::Error) -> Self {
+        let mut chain = Vec::new();
+        let mut source = err.source();
+        while let Some(cause) = source {
+            chain.push(cause.to_string());
+            source = cause.source();
+        }
+        self.error_chain = chain;
+        self
+    }
 }
 
 type ClientId = u64;
-type ClientSender = futures_util::stream::SplitSink<WebSocketStream<TcpStream>, Message>;
+
+/// A viewer-specified subscription narrowing which log entries get forwarded to it.
+///
+/// An absent/empty filter means "receive everything", which keeps existing viewers
+/// working without sending a `subscribe` frame.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct ClientFilter {
+    min_level: Option<LogLevel>,
+    #[serde(default)]
+    services: Vec<String>,
+    #[serde(default)]
+    contains: Option<String>,
+}
+
+/// Inbound control frame used to register a `ClientFilter`.
+#[derive(Debug, Deserialize)]
+struct SubscribeFrame {
+    subscribe: ClientFilter,
+}
+
+impl ClientFilter {
+    /// Whether `entry` should be forwarded to a client registered with this filter.
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(min_level) = self.min_level {
+            if entry.level < min_level {
+                return false;
+            }
+        }
+
+        if !self.services.is_empty() && !self.services.iter().any(|s| s == &entry.metadata.service)
+        {
+            return false;
+        }
+
+        if let Some(needle) = self.contains.as_deref().filter(|s| !s.is_empty()) {
+            let serialized = serde_json::to_string(&entry.args).unwrap_or_default();
+            if !serialized.contains(needle) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Inbound control frame used to present the shared secret during the auth handshake.
+#[derive(Debug, Deserialize)]
+struct AuthFrame {
+    auth: String,
+}
+
+/// Inbound control frame used to advertise supported compression encodings.
+#[derive(Debug, Deserialize)]
+struct AcceptEncodingFrame {
+    accept_encoding: Vec<String>,
+}
+
+/// A parsed inbound control frame, covering every shape a client may send over the
+/// connection's text channel.
+enum ControlFrame {
+    Subscribe(ClientFilter),
+    AcceptEncoding(Vec<String>),
+}
+
+impl ControlFrame {
+    fn parse(text: &str) -> Option<Self> {
+        if let Ok(frame) = serde_json::from_str::<SubscribeFrame>(text) {
+            return Some(ControlFrame::Subscribe(frame.subscribe));
+        }
+        if let Ok(frame) = serde_json::from_str::<AcceptEncodingFrame>(text) {
+            return Some(ControlFrame::AcceptEncoding(frame.accept_encoding));
+        }
+        None
+    }
+}
+
+/// Marker byte prepended to gzip-compressed binary frames, so the viewer can tell a
+/// compressed payload apart from any future binary framing this protocol might add.
+const GZIP_FRAME_MARKER: u8 = 0x01;
+
+/// Gzip-compress `payload`, returning the marker-prefixed bytes ready to send as a
+/// `Message::Binary` frame.
+fn compress_gzip_frame(payload: &str) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let _ = encoder.write_all(payload.as_bytes());
+    let gzipped = encoder.finish().unwrap_or_default();
+
+    let mut framed = Vec::with_capacity(gzipped.len() + 1);
+    framed.push(GZIP_FRAME_MARKER);
+    framed.extend_from_slice(&gzipped);
+    framed
+}
+
+/// Compare two strings in constant time, so a timing side-channel can't be used to
+/// guess the configured auth token one byte at a time.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Capacity of each client's outbound queue. Once full, new messages for that client
+/// are dropped rather than blocking `log` or the other clients.
+const OUTBOUND_QUEUE_CAPACITY: usize = 1024;
+
+/// A connected viewer: its outbound queue, its current subscription filter, its
+/// negotiated encoding and its dropped-message count.
+struct ClientHandle {
+    /// Bounded queue feeding this client's dedicated writer task. `log` only ever
+    /// `try_send`s here, so a stalled client can't block logging for everyone else.
+    queue_tx: mpsc::Sender<Message>,
+    filter: ClientFilter,
+    /// Whether this client negotiated gzip compression for outbound frames.
+    compress: bool,
+    /// Messages dropped because this client's queue was full.
+    dropped: AtomicU64,
+}
 
 /// Internal state for the SlogX server.
 struct SlogXState {
-    clients: HashMap<ClientId, ClientSender>,
+    clients: HashMap<ClientId, ClientHandle>,
     next_client_id: ClientId,
     service_name: String,
     initialized: bool,
+    /// Bounded backfill buffer so newly connected viewers can see recent history.
+    history: VecDeque<LogEntry>,
+    history_cap: usize,
+    /// Shared secret clients must present during the handshake. `None` disables auth.
+    auth_token: Option<String>,
+    stacktrace_policy: StacktracePolicy,
 }
 
 impl SlogXState {
@@ -164,8 +348,23 @@ impl SlogXState {
             next_client_id: 0,
             service_name: "rust-service".to_string(),
             initialized: false,
+            history: VecDeque::new(),
+            history_cap: 0,
+            auth_token: None,
+            stacktrace_policy: StacktracePolicy::default(),
         }
     }
+
+    /// Push an entry into the history ring buffer, evicting the oldest entry if over capacity.
+    fn push_history(&mut self, entry: LogEntry) {
+        if self.history_cap == 0 {
+            return;
+        }
+        if self.history.len() >= self.history_cap {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry);
+    }
 }
 
 /// The main SlogX struct for logging.
@@ -189,11 +388,35 @@ impl SlogX {
     }
 
     /// Initialize the WebSocket server on the specified port.
-    pub async fn start(&self, port: u16, service_name: &str) {
+    ///
+    /// `history_cap` bounds how many recent `LogEntry` values are retained so a client
+    /// connecting mid-run can be sent a backfill batch before streaming live logs. Pass
+    /// `0` to disable history replay.
+    ///
+    /// `auth_token`, when set, requires every connecting client to complete a handshake
+    /// by sending `{"auth": "<token>"}` as its first text frame before it is registered
+    /// and starts receiving logs.
+    ///
+    /// A client may also send `{"accept_encoding": ["gzip"]}` at any point to opt into
+    /// gzip-compressed `Message::Binary` frames instead of plain `Message::Text`.
+    ///
+    /// `stacktrace_policy` controls when a log entry pays the cost of capturing a
+    /// stacktrace; see [`StacktracePolicy`].
+    pub async fn start(
+        &self,
+        port: u16,
+        service_name: &str,
+        history_cap: usize,
+        auth_token: Option<&str>,
+        stacktrace_policy: StacktracePolicy,
+    ) {
         {
             let mut state = self.state.write().await;
             state.service_name = service_name.to_string();
             state.initialized = true;
+            state.history_cap = history_cap;
+            state.auth_token = auth_token.map(|s| s.to_string());
+            state.stacktrace_policy = stacktrace_policy;
         }
 
         let addr = format!("127.0.0.1:{}", port);
@@ -207,24 +430,101 @@ impl SlogX {
                 let state = state.clone();
                 tokio::spawn(async move {
                     if let Ok(ws_stream) = accept_async(stream).await {
-                        let (sender, mut receiver) = ws_stream.split();
+                        let (mut sender, mut receiver) = ws_stream.split();
+
+                        let auth_token = state.read().await.auth_token.clone();
+                        if let Some(expected_token) = auth_token {
+                            let authorized = match tokio::time::timeout(
+                                tokio::time::Duration::from_secs(5),
+                                receiver.next(),
+                            )
+                            .await
+                            {
+                                Ok(Some(Ok(Message::Text(text)))) => {
+                                    serde_json::from_str::<AuthFrame>(&text)
+                                        .map(|frame| constant_time_eq(&frame.auth, &expected_token))
+                                        .unwrap_or(false)
+                                }
+                                _ => false,
+                            };
+
+                            if !authorized {
+                                let _ = sender
+                                    .send(Message::Text(r#"{"error":"unauthorized"}"#.to_string()))
+                                    .await;
+                                let _ = sender.close().await;
+                                return;
+                            }
+                        }
+
+                        let (queue_tx, mut queue_rx) = mpsc::channel(OUTBOUND_QUEUE_CAPACITY);
 
                         let client_id = {
                             let mut state = state.write().await;
                             let id = state.next_client_id;
                             state.next_client_id += 1;
-                            state.clients.insert(id, sender);
+                            state.clients.insert(
+                                id,
+                                ClientHandle {
+                                    queue_tx: queue_tx.clone(),
+                                    filter: ClientFilter::default(),
+                                    compress: false,
+                                    dropped: AtomicU64::new(0),
+                                },
+                            );
+                            // Snapshot history and enqueue the backfill under the same write
+                            // lock used by `log`, so a concurrently-arriving entry can't be
+                            // both in the backfill and the live broadcast, and can't be
+                            // `try_send`'d into this client's queue ahead of the backfill
+                            // that precedes it.
+                            for entry in &state.history {
+                                if let Ok(payload) = serde_json::to_string(entry) {
+                                    let _ = queue_tx.try_send(Message::Text(payload));
+                                }
+                            }
                             id
                         };
 
-                        // Keep connection alive until client disconnects
+                        // Dedicated writer task: owns the socket's write half so a slow or
+                        // stalled client only ever blocks its own queue, never `log` or the
+                        // other clients' writer tasks.
+                        let writer_state = state.clone();
+                        tokio::spawn(async move {
+                            while let Some(msg) = queue_rx.recv().await {
+                                if sender.send(msg).await.is_err() {
+                                    break;
+                                }
+                            }
+                            writer_state.write().await.clients.remove(&client_id);
+                        });
+
+                        // Keep connection alive until client disconnects, applying
+                        // whichever control frame (filter, encoding, ...) it sends.
                         while let Some(msg) = receiver.next().await {
-                            if msg.is_err() {
-                                break;
+                            let msg = match msg {
+                                Ok(msg) => msg,
+                                Err(_) => break,
+                            };
+
+                            if let Message::Text(text) = msg {
+                                if let Some(control) = ControlFrame::parse(&text) {
+                                    let mut state = state.write().await;
+                                    if let Some(handle) = state.clients.get_mut(&client_id) {
+                                        match control {
+                                            ControlFrame::Subscribe(filter) => {
+                                                handle.filter = filter;
+                                            }
+                                            ControlFrame::AcceptEncoding(encodings) => {
+                                                handle.compress =
+                                                    encodings.iter().any(|e| e == "gzip");
+                                            }
+                                        }
+                                    }
+                                }
                             }
                         }
 
-                        // Remove client on disconnect
+                        // Remove client on disconnect (idempotent if the writer task already did).
                         let mut state = state.write().await;
                         state.clients.remove(&client_id);
                     }
@@ -253,11 +553,38 @@ impl SlogX {
 
     /// Internal logging function.
     async fn log(&self, level: LogLevel, args: Vec<Value>, file: &str, line: u32, function: &str) {
+        self.log_entry(level, args, file, line, function, None).await;
+    }
+
+    /// Log an error together with the causal chain from `err.source()`, so a viewer can
+    /// show the full chain of causes instead of just the top-level message.
+    async fn log_error(
+        &self,
+        err: &dyn std::error::Error,
+        args: Vec<Value>,
+        file: &str,
+        line: u32,
+        function: &str,
+    ) {
+        self.log_entry(LogLevel::Error, args, file, line, function, Some(err))
+            .await;
+    }
+
+    async fn log_entry(
+        &self,
+        level: LogLevel,
+        args: Vec<Value>,
+        file: &str,
+        line: u32,
+        function: &str,
+        err: Option<&dyn std::error::Error>,
+    ) {
         let state = self.state.read().await;
-        if !state.initialized || state.clients.is_empty() {
+        if !state.initialized {
             return;
         }
         let service_name = state.service_name.clone();
+        let stacktrace_policy = state.stacktrace_policy;
         drop(state);
 
         let entry = LogEntry::new(
@@ -267,25 +594,61 @@ impl SlogX {
             Some(file),
             Some(line),
             Some(function),
+            stacktrace_policy,
         );
+        let entry = match err {
+            Some(err) => entry.with_error_chain(err),
+            None => entry,
+        };
 
+        // Compressed once, lazily, and reused across every compressing client.
+        let mut compressed: Option<Vec<u8>> = None;
+
+        // Push history and broadcast under the same write lock: `try_send` is
+        // non-blocking, so holding the lock across both doesn't stall on a slow
+        // client, but it does keep the snapshot atomic. Without that, a client
+        // connecting between the two steps could see the entry in its backfill
+        // and again via the live broadcast. History is recorded unconditionally
+        // (push_history is a no-op when history_cap is 0) so a viewer that joins
+        // later still gets everything logged before it connected, even if no
+        // one was connected when it happened.
+        let mut state = self.state.write().await;
+        state.push_history(entry.clone());
+        if state.clients.is_empty() {
+            return;
+        }
         let payload = match serde_json::to_string(&entry) {
             Ok(p) => p,
             Err(_) => return,
         };
+        for handle in state.clients.values() {
+            if !handle.filter.matches(&entry) {
+                continue;
+            }
 
-        let mut state = self.state.write().await;
-        let mut disconnected = Vec::new();
+            let msg = if handle.compress {
+                let framed = compressed.get_or_insert_with(|| compress_gzip_frame(&payload));
+                Message::Binary(framed.clone())
+            } else {
+                Message::Text(payload.clone())
+            };
 
-        for (id, sender) in state.clients.iter_mut() {
-            if sender.send(Message::Text(payload.clone())).await.is_err() {
-                disconnected.push(*id);
+            if let Err(mpsc::error::TrySendError::Full(_)) = handle.queue_tx.try_send(msg) {
+                handle.dropped.fetch_add(1, Ordering::Relaxed);
             }
         }
+    }
 
-        for id in disconnected {
-            state.clients.remove(&id);
-        }
+    /// Total number of outbound messages dropped across all connected clients because
+    /// their queue was full (see `OUTBOUND_QUEUE_CAPACITY`).
+    pub async fn dropped_messages(&self) -> u64 {
+        self.state
+            .read()
+            .await
+            .clients
+            .values()
+            .map(|handle| handle.dropped.load(Ordering::Relaxed))
+            .sum()
     }
 }
 
@@ -302,17 +665,29 @@ fn get_instance() -> &'static SlogX {
 /// * `is_dev` - Required. Must be true to enable slogx. Prevents accidental production use.
 /// * `port` - WebSocket server port
 /// * `service_name` - Service name for log metadata
+/// * `history_cap` - Number of recent log entries to replay to newly connected viewers (0 disables)
+/// * `auth_token` - Optional shared secret clients must present before receiving logs
+/// * `stacktrace_policy` - When a log entry pays the cost of capturing a stacktrace
 ///
 /// # Example
 /// ```ignore
 /// let is_dev = std::env::var("ENV").unwrap_or_default() != "production";
-/// slogx::init(is_dev, 8080, "my-service").await;
+/// slogx::init(is_dev, 8080, "my-service", 100, None, slogx::StacktracePolicy::ErrorsOnly).await;
 /// ```
-pub async fn init(is_dev: bool, port: u16, service_name: &str) {
+pub async fn init(
+    is_dev: bool,
+    port: u16,
+    service_name: &str,
+    history_cap: usize,
+    auth_token: Option<&str>,
+    stacktrace_policy: StacktracePolicy,
+) {
     if !is_dev {
         return;
     }
-    get_instance().start(port, service_name).await;
+    get_instance()
+        .start(port, service_name, history_cap, auth_token, stacktrace_policy)
+        .await;
 }
 
 /// Check if the global server is initialized.
@@ -336,6 +711,20 @@ pub async fn __log_at(level: LogLevel, args: Vec<Value>, file: &str, line: u32,
     get_instance().log(level, args, file, line, function).await;
 }
 
+/// Internal: Log an error with its `source()` chain (used by `error_chain!`).
+#[doc(hidden)]
+pub async fn __log_error_at(
+    err: &dyn std::error::Error,
+    args: Vec<Value>,
+    file: &str,
+    line: u32,
+    function: &str,
+) {
+    get_instance()
+        .log_error(err, args, file, line, function)
+        .await;
+}
+
 /// Helper macro to convert a value to JSON (internal use).
 /// Handles JSON object literals `{ ... }`, arrays `[ ... ]`, and expressions.
 #[macro_export]
@@ -414,10 +803,27 @@ macro_rules! error {
     }};
 }
 
+/// Log an error together with its `source()` chain, so a viewer shows the full chain
+/// of causes rather than a flat message.
+///
+/// # Examples
+/// ```ignore
+/// if let Err(e) = connect().await {
+///     slogx::error_chain!(&e, "Connection failed", { "host": "db.example.com" });
+/// }
+/// ```
+#[macro_export]
+macro_rules! error_chain {
+    ($err:expr, $($arg:tt),+ $(,)?) => {{
+        let args: Vec<serde_json::Value> = vec![$($crate::__to_json!($arg)),+];
+        $crate::__log_error_at($err, args, file!(), line!(), module_path!()).await
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use futures_util::StreamExt;
+    use futures_util::{SinkExt, StreamExt};
     use tokio_tungstenite::connect_async;
 
     // Helper to create a fresh instance for testing (bypasses global singleton)
@@ -436,6 +842,7 @@ mod tests {
             Some("handler.rs"),
             Some(42),
             Some("handle_request"),
+            StacktracePolicy::Always,
         );
 
         assert_eq!(entry.metadata.service, "my-service");
@@ -446,11 +853,11 @@ mod tests {
     }
 
     #[test]
-    fn test_log_entry_includes_stacktrace_for_all_levels() {
-        let error = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None);
-        let info = LogEntry::new(LogLevel::Info, vec![], "svc", None, None, None);
-        let debug = LogEntry::new(LogLevel::Debug, vec![], "svc", None, None, None);
-        let warn = LogEntry::new(LogLevel::Warn, vec![], "svc", None, None, None);
+    fn test_log_entry_includes_stacktrace_for_all_levels_when_always() {
+        let error = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, StacktracePolicy::Always);
+        let info = LogEntry::new(LogLevel::Info, vec![], "svc", None, None, None, StacktracePolicy::Always);
+        let debug = LogEntry::new(LogLevel::Debug, vec![], "svc", None, None, None, StacktracePolicy::Always);
+        let warn = LogEntry::new(LogLevel::Warn, vec![], "svc", None, None, None, StacktracePolicy::Always);
 
         assert!(error.stacktrace.is_some());
         assert!(info.stacktrace.is_some());
@@ -462,6 +869,34 @@ mod tests {
         assert!(!info.stacktrace.as_ref().unwrap().is_empty());
     }
 
+    #[test]
+    fn test_stacktrace_policy_never_omits_stacktrace() {
+        let entry = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, StacktracePolicy::Never);
+        assert!(entry.stacktrace.is_none());
+    }
+
+    #[test]
+    fn test_stacktrace_policy_errors_only() {
+        let error = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, StacktracePolicy::ErrorsOnly);
+        let info = LogEntry::new(LogLevel::Info, vec![], "svc", None, None, None, StacktracePolicy::ErrorsOnly);
+
+        assert!(error.stacktrace.is_some());
+        assert!(info.stacktrace.is_none());
+    }
+
+    #[test]
+    fn test_stacktrace_policy_at_level() {
+        let policy = StacktracePolicy::AtLevel(LogLevel::Warn);
+
+        let warn = LogEntry::new(LogLevel::Warn, vec![], "svc", None, None, None, policy);
+        let error = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, policy);
+        let info = LogEntry::new(LogLevel::Info, vec![], "svc", None, None, None, policy);
+
+        assert!(warn.stacktrace.is_some());
+        assert!(error.stacktrace.is_some());
+        assert!(info.stacktrace.is_none());
+    }
+
     #[test]
     fn test_log_entry_preserves_args_order() {
         let entry = LogEntry::new(
@@ -475,6 +910,7 @@ mod tests {
             None,
             None,
             None,
+            StacktracePolicy::Always,
         );
 
         assert_eq!(entry.args[0], "first");
@@ -493,21 +929,21 @@ mod tests {
     #[tokio::test]
     async fn test_slogx_init_sets_initialized() {
         let slogx = test_instance();
-        slogx.start(19001, "test-service").await;
+        slogx.start(19001, "test-service", 0, None, StacktracePolicy::Always).await;
         assert!(slogx.is_initialized().await);
     }
 
     #[tokio::test]
     async fn test_slogx_init_sets_service_name() {
         let slogx = test_instance();
-        slogx.start(19002, "custom-name").await;
+        slogx.start(19002, "custom-name", 0, None, StacktracePolicy::Always).await;
         assert_eq!(slogx.service_name().await, "custom-name");
     }
 
     #[tokio::test]
     async fn test_slogx_starts_with_no_clients() {
         let slogx = test_instance();
-        slogx.start(19003, "test").await;
+        slogx.start(19003, "test", 0, None, StacktracePolicy::Always).await;
         assert_eq!(slogx.client_count().await, 0);
     }
 
@@ -516,7 +952,7 @@ mod tests {
         let slogx1 = test_instance();
         let slogx2 = slogx1.clone();
 
-        slogx1.start(19004, "shared-service").await;
+        slogx1.start(19004, "shared-service", 0, None, StacktracePolicy::Always).await;
 
         // Both should see the initialization
         assert!(slogx2.is_initialized().await);
@@ -535,7 +971,7 @@ mod tests {
     #[tokio::test]
     async fn test_log_with_init_but_no_clients_does_not_panic() {
         let slogx = test_instance();
-        slogx.start(19005, "test").await;
+        slogx.start(19005, "test", 0, None, StacktracePolicy::Always).await;
         // Should complete without panic
         slogx.log(LogLevel::Info, vec![serde_json::json!("test")], "f", 1, "fn").await;
     }
@@ -545,7 +981,7 @@ mod tests {
     #[tokio::test]
     async fn test_client_connection_increments_count() {
         let slogx = test_instance();
-        slogx.start(19006, "test").await;
+        slogx.start(19006, "test", 0, None, StacktracePolicy::Always).await;
 
         assert_eq!(slogx.client_count().await, 0);
 
@@ -561,7 +997,7 @@ mod tests {
     #[tokio::test]
     async fn test_client_receives_log_message() {
         let slogx = test_instance();
-        slogx.start(19007, "msg-test").await;
+        slogx.start(19007, "msg-test", 0, None, StacktracePolicy::Always).await;
 
         // Connect a client
         let (ws, _) = connect_async("ws://127.0.0.1:19007").await.unwrap();
@@ -596,7 +1032,7 @@ mod tests {
     #[tokio::test]
     async fn test_all_log_levels_work() {
         let slogx = test_instance();
-        slogx.start(19008, "levels-test").await;
+        slogx.start(19008, "levels-test", 0, None, StacktracePolicy::Always).await;
 
         let (ws, _) = connect_async("ws://127.0.0.1:19008").await.unwrap();
         let (_, mut read) = ws.split();
@@ -621,7 +1057,7 @@ mod tests {
     #[tokio::test]
     async fn test_multiple_clients_receive_same_message() {
         let slogx = test_instance();
-        slogx.start(19009, "multi-client").await;
+        slogx.start(19009, "multi-client", 0, None, StacktracePolicy::Always).await;
 
         // Connect two clients
         let (ws1, _) = connect_async("ws://127.0.0.1:19009").await.unwrap();
@@ -658,7 +1094,7 @@ mod tests {
     #[tokio::test]
     async fn test_client_disconnect_decrements_count() {
         let slogx = test_instance();
-        slogx.start(19010, "disconnect-test").await;
+        slogx.start(19010, "disconnect-test", 0, None, StacktracePolicy::Always).await;
 
         let (ws, _) = connect_async("ws://127.0.0.1:19010").await.unwrap();
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -669,4 +1105,405 @@ mod tests {
 
         assert_eq!(slogx.client_count().await, 0);
     }
+
+    // --- History replay tests ---
+
+    #[tokio::test]
+    async fn test_late_joiner_receives_history_backfill() {
+        let slogx = test_instance();
+        slogx.start(19011, "history-test", 10, None, StacktracePolicy::Always).await;
+
+        // Emit a couple of logs before anyone connects.
+        slogx.log(LogLevel::Info, vec![serde_json::json!("first")], "f", 1, "fn").await;
+        slogx.log(LogLevel::Info, vec![serde_json::json!("second")], "f", 2, "fn").await;
+
+        // Connect after the fact.
+        let (ws, _) = connect_async("ws://127.0.0.1:19011").await.unwrap();
+        let (_, mut read) = ws.split();
+
+        let msg1 = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+        let msg2 = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry1: LogEntry = serde_json::from_str(&msg1.into_text().unwrap()).unwrap();
+        let entry2: LogEntry = serde_json::from_str(&msg2.into_text().unwrap()).unwrap();
+
+        assert_eq!(entry1.args[0], "first");
+        assert_eq!(entry2.args[0], "second");
+    }
+
+    #[tokio::test]
+    async fn test_history_respects_capacity() {
+        let slogx = test_instance();
+        slogx.start(19012, "history-cap-test", 2, None, StacktracePolicy::Always).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("a")], "f", 1, "fn").await;
+        slogx.log(LogLevel::Info, vec![serde_json::json!("b")], "f", 2, "fn").await;
+        slogx.log(LogLevel::Info, vec![serde_json::json!("c")], "f", 3, "fn").await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19012").await.unwrap();
+        let (_, mut read) = ws.split();
+
+        let msg1 = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+        let msg2 = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry1: LogEntry = serde_json::from_str(&msg1.into_text().unwrap()).unwrap();
+        let entry2: LogEntry = serde_json::from_str(&msg2.into_text().unwrap()).unwrap();
+
+        // Oldest entry ("a") should have been evicted.
+        assert_eq!(entry1.args[0], "b");
+        assert_eq!(entry2.args[0], "c");
+    }
+
+    #[tokio::test]
+    async fn test_no_history_sent_when_disabled() {
+        let slogx = test_instance();
+        slogx.start(19013, "no-history-test", 0, None, StacktracePolicy::Always).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("ignored")], "f", 1, "fn").await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19013").await.unwrap();
+        let (_, mut read) = ws.split();
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("live")], "f", 2, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.args[0], "live");
+    }
+
+    // --- Subscription filtering tests ---
+
+    #[tokio::test]
+    async fn test_filter_drops_entries_below_min_level() {
+        let slogx = test_instance();
+        slogx.start(19014, "filter-level-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19014").await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"subscribe":{"min_level":"WARN"}}"#.to_string()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("skip me")], "f", 1, "fn").await;
+        slogx.log(LogLevel::Error, vec![serde_json::json!("keep me")], "f", 2, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.args[0], "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_filter_matches_by_service() {
+        let slogx = test_instance();
+        slogx.start(19015, "api", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19015").await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"subscribe":{"services":["other"]}}"#.to_string()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("from api")], "f", 1, "fn").await;
+
+        let result = tokio::time::timeout(
+            tokio::time::Duration::from_millis(200),
+            read.next(),
+        ).await;
+
+        assert!(result.is_err(), "client subscribed to a different service should not receive this entry");
+    }
+
+    #[tokio::test]
+    async fn test_filter_matches_by_contains() {
+        let slogx = test_instance();
+        slogx.start(19016, "contains-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19016").await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"subscribe":{"contains":"timeout"}}"#.to_string()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("all good")], "f", 1, "fn").await;
+        slogx.log(LogLevel::Error, vec![serde_json::json!("connection timeout")], "f", 2, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.args[0], "connection timeout");
+    }
+
+    #[tokio::test]
+    async fn test_no_filter_receives_everything() {
+        let slogx = test_instance();
+        slogx.start(19017, "no-filter-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19017").await.unwrap();
+        let (_, mut read) = ws.split();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Debug, vec![serde_json::json!("anything")], "f", 1, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.args[0], "anything");
+    }
+
+    // --- Auth handshake tests ---
+
+    #[tokio::test]
+    async fn test_no_auth_token_allows_any_client() {
+        let slogx = test_instance();
+        slogx.start(19018, "no-auth-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19018").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        assert_eq!(slogx.client_count().await, 1);
+        drop(ws);
+    }
+
+    #[tokio::test]
+    async fn test_correct_token_registers_client() {
+        let slogx = test_instance();
+        slogx.start(19019, "auth-test", 0, Some("s3cr3t"), StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19019").await.unwrap();
+        let (mut write, _read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"auth":"s3cr3t"}"#.to_string()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        assert_eq!(slogx.client_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_token_is_never_registered() {
+        let slogx = test_instance();
+        slogx.start(19020, "auth-reject-test", 0, Some("s3cr3t"), StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19020").await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"auth":"wrong"}"#.to_string()))
+            .await
+            .unwrap();
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        assert_eq!(msg.into_text().unwrap(), r#"{"error":"unauthorized"}"#);
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        assert_eq!(slogx.client_count().await, 0);
+    }
+
+    // --- Compression negotiation tests ---
+
+    #[tokio::test]
+    async fn test_client_without_negotiation_gets_plain_text() {
+        let slogx = test_instance();
+        slogx.start(19021, "no-compress-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19021").await.unwrap();
+        let (_, mut read) = ws.split();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("plain")], "f", 1, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        assert!(msg.is_text());
+    }
+
+    #[tokio::test]
+    async fn test_client_negotiating_gzip_gets_compressed_binary() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let slogx = test_instance();
+        slogx.start(19022, "compress-test", 0, None, StacktracePolicy::Always).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19022").await.unwrap();
+        let (mut write, mut read) = ws.split();
+
+        write
+            .send(Message::Text(r#"{"accept_encoding":["gzip"]}"#.to_string()))
+            .await
+            .unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        slogx.log(LogLevel::Info, vec![serde_json::json!("zipped")], "f", 1, "fn").await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let bytes = msg.into_data();
+        assert_eq!(bytes[0], GZIP_FRAME_MARKER);
+
+        let mut decoder = GzDecoder::new(&bytes[1..]);
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed).unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&decompressed).unwrap();
+        assert_eq!(entry.args[0], "zipped");
+    }
+
+    // --- Per-client queue / backpressure tests ---
+
+    #[tokio::test]
+    async fn test_slow_client_does_not_block_other_clients() {
+        let slogx = test_instance();
+        slogx.start(19023, "backpressure-test", 0, None, StacktracePolicy::Always).await;
+
+        // A "slow" client that never reads from its socket.
+        let (_slow_ws, _) = connect_async("ws://127.0.0.1:19023").await.unwrap();
+
+        // A normal client that does read.
+        let (fast_ws, _) = connect_async("ws://127.0.0.1:19023").await.unwrap();
+        let (_, mut fast_read) = fast_ws.split();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let start = std::time::Instant::now();
+        slogx.log(LogLevel::Info, vec![serde_json::json!("fast")], "f", 1, "fn").await;
+        assert!(start.elapsed() < tokio::time::Duration::from_millis(200));
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            fast_read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.args[0], "fast");
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_messages_without_disconnecting() {
+        let slogx = test_instance();
+        slogx.start(19024, "drop-test", 0, None, StacktracePolicy::Always).await;
+
+        let (_ws, _) = connect_async("ws://127.0.0.1:19024").await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        for i in 0..(OUTBOUND_QUEUE_CAPACITY * 2) {
+            slogx.log(LogLevel::Info, vec![serde_json::json!(i)], "f", 1, "fn").await;
+        }
+
+        assert_eq!(slogx.client_count().await, 1);
+    }
+
+    // --- Error chain tests ---
+
+    #[derive(Debug)]
+    struct RootCause;
+
+    impl std::fmt::Display for RootCause {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "disk full")
+        }
+    }
+    impl std::error::Error for RootCause {}
+
+    #[derive(Debug)]
+    struct WrappedError(RootCause);
+
+    impl std::fmt::Display for WrappedError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "failed to write file")
+        }
+    }
+    impl std::error::Error for WrappedError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_with_error_chain_walks_source_chain() {
+        let err = WrappedError(RootCause);
+        let entry = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, StacktracePolicy::Never)
+            .with_error_chain(&err);
+
+        assert_eq!(entry.error_chain, vec!["disk full".to_string()]);
+    }
+
+    #[test]
+    fn test_with_error_chain_empty_when_no_source() {
+        let err = RootCause;
+        let entry = LogEntry::new(LogLevel::Error, vec![], "svc", None, None, None, StacktracePolicy::Never)
+            .with_error_chain(&err);
+
+        assert!(entry.error_chain.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_error_sends_error_chain_to_clients() {
+        let slogx = test_instance();
+        slogx.start(19025, "error-chain-test", 0, None, StacktracePolicy::Never).await;
+
+        let (ws, _) = connect_async("ws://127.0.0.1:19025").await.unwrap();
+        let (_, mut read) = ws.split();
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        let err = WrappedError(RootCause);
+        slogx
+            .log_error(&err, vec![serde_json::json!("save failed")], "f", 1, "fn")
+            .await;
+
+        let msg = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            read.next(),
+        ).await.unwrap().unwrap().unwrap();
+
+        let entry: LogEntry = serde_json::from_str(&msg.into_text().unwrap()).unwrap();
+        assert_eq!(entry.level, LogLevel::Error);
+        assert_eq!(entry.error_chain, vec!["disk full".to_string()]);
+    }
 }